@@ -1,37 +1,20 @@
-use fingerprint_sensor::{
-    Device, BADLOCATION, ENROLLMISMATCH, FEATUREFAIL, FLASHERR, IMAGEFAIL, IMAGEMESS, INVALIDIMAGE,
-    NOFINGER, OK,
-};
+use fingerprint_sensor::{ConfirmationCode, Device, DeviceOptions, Error};
 use serialport::{self};
 use std::io::{self, Write};
 use std::process::exit;
 use std::thread::sleep;
 use std::time::Duration;
 
-fn get_fingerprint(device: &mut Device) -> io::Result<()> {
-    if device.get_image()? != OK {
-        return Err(io::Error::new(io::ErrorKind::Other, "Failed to get image"));
-    }
+fn get_fingerprint(device: &mut Device) -> Result<(), Error> {
+    device.get_image()?;
 
     println!("Templating...");
-
-    if device.image_2_tz(1)? != OK {
-        return Err(io::Error::new(
-            io::ErrorKind::Other,
-            "Failed to convert image to template",
-        ));
-    }
+    device.image_2_tz(1)?;
 
     println!("Searching...");
+    device.finger_search()?;
 
-    if device.finger_search()? != OK {
-        return Err(io::Error::new(
-            io::ErrorKind::Other,
-            "Failed to search for fingerprint",
-        ));
-    } else {
-        return Ok(());
-    }
+    Ok(())
 }
 
 fn get_num(max_number: u16) -> u16 {
@@ -54,7 +37,7 @@ fn get_num(max_number: u16) -> u16 {
     }
 }
 
-fn enroll_finger(location: u16, device: &mut Device) -> io::Result<()> {
+fn enroll_finger(location: u16, device: &mut Device) -> Result<(), Error> {
     for fingerimg in 1..=2 {
         if fingerimg == 1 {
             print!("Place finger on sensor...");
@@ -63,90 +46,83 @@ fn enroll_finger(location: u16, device: &mut Device) -> io::Result<()> {
         }
 
         loop {
-            let i = device.get_image()?;
-            match i {
-                OK => {
+            match device.get_image() {
+                Ok(()) => {
                     println!("Image taken");
                     break;
                 }
-                NOFINGER => print!("."),
-                IMAGEFAIL => {
+                Err(Error::Protocol(ConfirmationCode::NoFinger)) => print!("."),
+                Err(Error::Protocol(ConfirmationCode::ImageFail)) => {
                     println!("Imaging error");
-                    return Err(io::Error::new(io::ErrorKind::Other, "Imaging error"));
+                    return Err(Error::Protocol(ConfirmationCode::ImageFail));
                 }
-                _ => {
+                Err(e) => {
                     println!("Other error");
-                    return Err(io::Error::new(io::ErrorKind::Other, "Other error"));
+                    return Err(e);
                 }
             }
         }
 
         print!("Templating...");
-        let i = device.image_2_tz(fingerimg)?;
-        match i {
-            OK => println!("Templated"),
-            IMAGEMESS => {
+        match device.image_2_tz(fingerimg) {
+            Ok(()) => println!("Templated"),
+            Err(e @ Error::Protocol(ConfirmationCode::ImageMess)) => {
                 println!("Image too messy");
-                return Err(io::Error::new(io::ErrorKind::Other, "Image too messy"));
+                return Err(e);
             }
-            FEATUREFAIL => {
+            Err(e @ Error::Protocol(ConfirmationCode::FeatureFail)) => {
                 println!("Could not identify features");
-                return Err(io::Error::new(
-                    io::ErrorKind::Other,
-                    "Could not identify features",
-                ));
+                return Err(e);
             }
-            INVALIDIMAGE => {
+            Err(e @ Error::Protocol(ConfirmationCode::InvalidImage)) => {
                 println!("Image invalid");
-                return Err(io::Error::new(io::ErrorKind::Other, "Image invalid"));
+                return Err(e);
             }
-            _ => {
+            Err(e) => {
                 println!("Other error");
-                return Err(io::Error::new(io::ErrorKind::Other, "Other error"));
+                return Err(e);
             }
         }
 
         if fingerimg == 1 {
             println!("Remove finger");
             sleep(Duration::from_secs(1));
-            let img = match device.get_image() {
-                Ok(i) => i,
-                Err(_) => return Err(io::Error::new(io::ErrorKind::Other, "Failed to get image")),
-            };
-
-            while img != NOFINGER {}
+            loop {
+                match device.get_image() {
+                    Err(Error::Protocol(ConfirmationCode::NoFinger)) => break,
+                    _ => continue,
+                }
+            }
         }
     }
 
     print!("Creating model...");
-    let i = device.create_model()?;
-    match i {
-        OK => println!("Created"),
-        ENROLLMISMATCH => {
+    match device.create_model() {
+        Ok(()) => println!("Created"),
+        Err(e @ Error::Protocol(ConfirmationCode::EnrollMismatch)) => {
             println!("Prints did not match");
-            return Err(io::Error::new(io::ErrorKind::Other, "Prints did not match"));
+            return Err(e);
         }
-        _ => {
+        Err(e) => {
             println!("Other error");
-            return Err(io::Error::new(io::ErrorKind::Other, "Other error"));
+            return Err(e);
         }
     }
 
     print!("Storing model #{}...", location);
-    let i = device.store_model(location as u16, 1)?;
-    match i {
-        OK => println!("Stored"),
-        BADLOCATION => {
+    match device.store_model(location, 1) {
+        Ok(()) => println!("Stored"),
+        Err(e @ Error::Protocol(ConfirmationCode::BadLocation)) => {
             println!("Bad storage location");
-            return Err(io::Error::new(io::ErrorKind::Other, "Bad storage location"));
+            return Err(e);
         }
-        FLASHERR => {
+        Err(e @ Error::Protocol(ConfirmationCode::FlashErr)) => {
             println!("Flash storage error");
-            return Err(io::Error::new(io::ErrorKind::Other, "Flash storage error"));
+            return Err(e);
         }
-        _ => {
+        Err(e) => {
             println!("Other error");
-            return Err(io::Error::new(io::ErrorKind::Other, "Other error"));
+            return Err(e);
         }
     }
 
@@ -162,12 +138,16 @@ fn main() -> io::Result<()> {
         .open()
         .expect("Failed to open serial port");
 
-    let address = vec![0xFF; 4];
-    let password = vec![0; 4];
-    let mut device = Device::new(address, password, uart);
+    let options = DeviceOptions {
+        address: vec![0xFF; 4],
+        password: vec![0; 4],
+        read_timeout_ms: 900,
+        write_timeout_ms: 900,
+    };
+    let mut device = Device::new(uart, options);
 
     match device.count_templates() {
-        Ok(_) => println!("Template count: {}", device.template_count),
+        Ok(()) => println!("Template count: {}", device.template_count),
         Err(e) => println!("Failed to count templates: {}", e),
     }
 
@@ -0,0 +1,175 @@
+//! Async command API.
+//!
+//! [`crate::Device`] blocks the calling thread on every `read_exact` while
+//! it waits for an ACK, which is fine on its own but doesn't compose with
+//! an application that's already running a `tokio` event loop. `AsyncDevice`
+//! offers the enroll/search commands built on `send_command`, which writes a packet
+//! and returns a future that resolves once the matching ACK arrives, so an
+//! enroll/search loop can `.await` it alongside other I/O instead of
+//! busy-waiting a thread. `Device` itself is unaffected and remains the
+//! simpler entry point for callers that don't need a shared runtime.
+
+use byteorder::{BigEndian, ByteOrder};
+use std::io;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::error::{self, Error};
+
+const STARTCODE: u16 = 0xEF01;
+const COMMANDPACKET: u8 = 0x1;
+
+const GETIMAGE: u8 = 0x01;
+const IMAGE2TZ: u8 = 0x02;
+const FINGERPRINTSEARCH: u8 = 0x04;
+const REGMODEL: u8 = 0x05;
+const STORE: u8 = 0x06;
+const DELETE: u8 = 0x0C;
+
+/// An async byte-oriented channel, the `tokio` counterpart to
+/// [`crate::Transport`].
+pub trait AsyncTransport: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> AsyncTransport for T {}
+
+/// Async command surface for the sensor, built on an [`AsyncTransport`].
+///
+/// Provides async versions of [`crate::Device`]'s `verify_password`,
+/// `get_image`, `image_2_tz`, `finger_search`, `create_model`,
+/// `store_model` and `delete_model`; each is a future that resolves once
+/// the matching ACK arrives instead of blocking the calling thread on
+/// `read_exact`. The other `Device` commands (system parameters, template
+/// count, template transfer, handshake, index table and 1:1 matching) have
+/// no async counterpart, and the library size is set with
+/// `set_library_size` rather than read from the sensor.
+pub struct AsyncDevice<T: AsyncTransport> {
+    transport: T,
+    address: Vec<u8>,
+    password: Vec<u8>,
+    library_size: Option<u16>,
+    finger_id: u16,
+    confidence: u16,
+}
+
+impl<T: AsyncTransport> AsyncDevice<T> {
+    pub fn new(transport: T, address: Vec<u8>, password: Vec<u8>) -> Self {
+        Self {
+            transport,
+            address,
+            password,
+            library_size: None,
+            finger_id: 0,
+            confidence: 0,
+        }
+    }
+
+    pub fn finger_id(&self) -> u16 {
+        self.finger_id
+    }
+
+    pub fn confidence(&self) -> u16 {
+        self.confidence
+    }
+
+    pub fn set_library_size(&mut self, library_size: u16) {
+        self.library_size = Some(library_size);
+    }
+
+    pub async fn verify_password(&mut self) -> Result<(), Error> {
+        let password = self.password.clone();
+        let r = self.send_command(&password, 12).await?;
+        error::check_code(r[0])
+    }
+
+    /// Writes a command packet and returns once the matching ACK packet has
+    /// been read back, without blocking a thread on the round trip.
+    pub async fn send_command(&mut self, data: &[u8], expected: usize) -> Result<Vec<u8>, Error> {
+        self.send_packet(data).await?;
+        self.get_packet(expected).await
+    }
+
+    async fn send_packet(&mut self, data: &[u8]) -> io::Result<()> {
+        let mut packet = vec![(STARTCODE >> 8) as u8, (STARTCODE & 0xFF) as u8];
+
+        packet.extend_from_slice(&self.address);
+        packet.push(COMMANDPACKET);
+
+        let length = data.len() + 2;
+        packet.push((length >> 8) as u8);
+        packet.push((length & 0xFF) as u8);
+
+        packet.extend_from_slice(data);
+
+        let checksum: u16 = packet[6..].iter().map(|&byte| byte as u16).sum();
+        packet.push((checksum >> 8) as u8);
+        packet.push((checksum & 0xFF) as u8);
+
+        self.transport.write_all(&packet).await
+    }
+
+    async fn get_packet(&mut self, expected: usize) -> Result<Vec<u8>, Error> {
+        let mut res = vec![0; expected];
+        self.transport.read_exact(&mut res).await?;
+
+        crate::packet::parse_ack_packet(&res, &self.address)
+    }
+
+    pub async fn get_image(&mut self) -> Result<(), Error> {
+        let r = self.send_command(&[GETIMAGE], 12).await?;
+        error::check_code(r[0])
+    }
+
+    pub async fn image_2_tz(&mut self, slot: u8) -> Result<(), Error> {
+        let r = self.send_command(&[IMAGE2TZ, slot], 12).await?;
+        error::check_code(r[0])
+    }
+
+    pub async fn finger_search(&mut self) -> Result<(), Error> {
+        let capacity = self
+            .library_size
+            .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "Library size not set"))?;
+
+        let r = self
+            .send_command(
+                &[
+                    FINGERPRINTSEARCH,
+                    0x01,
+                    0x00,
+                    0x00,
+                    (capacity >> 8) as u8,
+                    (capacity & 0xFF) as u8,
+                ],
+                16,
+            )
+            .await?;
+
+        self.finger_id = BigEndian::read_u16(&r[1..3]);
+        self.confidence = BigEndian::read_u16(&r[3..5]);
+
+        error::check_code(r[0])
+    }
+
+    pub async fn create_model(&mut self) -> Result<(), Error> {
+        let r = self.send_command(&[REGMODEL], 12).await?;
+        error::check_code(r[0])
+    }
+
+    pub async fn store_model(&mut self, location: u16, slot: u8) -> Result<(), Error> {
+        let r = self
+            .send_command(
+                &[STORE, slot, (location >> 8) as u8, (location & 0xFF) as u8],
+                12,
+            )
+            .await?;
+        error::check_code(r[0])
+    }
+
+    pub async fn delete_model(&mut self, location: u16) -> Result<(), Error> {
+        let high_byte = (location >> 8) as u8;
+        let low_byte = (location & 0xFF) as u8;
+
+        let r = self
+            .send_command(&[DELETE, high_byte, low_byte, 0x00, 0x01], 12)
+            .await?;
+        error::check_code(r[0])
+    }
+}
@@ -0,0 +1,213 @@
+//! Typed confirmation codes and the crate-level error type.
+//!
+//! The sensor reports the outcome of every command as a single status byte
+//! in the ACK packet. Rather than have callers compare that byte against a
+//! pile of `pub const` magic numbers, [`ConfirmationCode`] gives it a name,
+//! and [`Error`] wraps it alongside transport failures so a caller can
+//! match on what actually went wrong instead of an opaque `u8`.
+
+use std::fmt;
+use std::io;
+
+/// Confirmation codes the sensor returns in the first byte of an ACK
+/// packet, per the module's command reference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfirmationCode {
+    Ok,
+    PacketReceiveErr,
+    NoFinger,
+    ImageFail,
+    ImageMess,
+    FeatureFail,
+    NoMatch,
+    NotFound,
+    EnrollMismatch,
+    BadLocation,
+    DbRangeErr,
+    UploadFeatureFail,
+    PacketResponseFail,
+    UploadImageFail,
+    DeleteFail,
+    DbClearFail,
+    WrongPassword,
+    InvalidImage,
+    FlashErr,
+    InvalidReg,
+    WrongNotepadPage,
+    CommPortFail,
+}
+
+impl ConfirmationCode {
+    /// Decodes a raw status byte, returning the byte back on the `Err` side
+    /// if it isn't one of the codes defined in the command reference.
+    pub fn from_u8(byte: u8) -> Result<Self, u8> {
+        match byte {
+            0x00 => Ok(Self::Ok),
+            0x01 => Ok(Self::PacketReceiveErr),
+            0x02 => Ok(Self::NoFinger),
+            0x03 => Ok(Self::ImageFail),
+            0x06 => Ok(Self::ImageMess),
+            0x07 => Ok(Self::FeatureFail),
+            0x08 => Ok(Self::NoMatch),
+            0x09 => Ok(Self::NotFound),
+            0x0A => Ok(Self::EnrollMismatch),
+            0x0B => Ok(Self::BadLocation),
+            0x0C => Ok(Self::DbRangeErr),
+            0x0D => Ok(Self::UploadFeatureFail),
+            0x0E => Ok(Self::PacketResponseFail),
+            0x0F => Ok(Self::UploadImageFail),
+            0x10 => Ok(Self::DeleteFail),
+            0x11 => Ok(Self::DbClearFail),
+            0x13 => Ok(Self::WrongPassword),
+            0x15 => Ok(Self::InvalidImage),
+            0x18 => Ok(Self::FlashErr),
+            0x1A => Ok(Self::InvalidReg),
+            0x1C => Ok(Self::WrongNotepadPage),
+            0x1D => Ok(Self::CommPortFail),
+            other => Err(other),
+        }
+    }
+}
+
+impl TryFrom<u8> for ConfirmationCode {
+    type Error = u8;
+
+    fn try_from(byte: u8) -> Result<Self, u8> {
+        Self::from_u8(byte)
+    }
+}
+
+impl fmt::Display for ConfirmationCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            Self::Ok => "ok",
+            Self::PacketReceiveErr => "packet receive error",
+            Self::NoFinger => "no finger detected",
+            Self::ImageFail => "failed to enroll the finger",
+            Self::ImageMess => "image too messy",
+            Self::FeatureFail => "could not identify features",
+            Self::NoMatch => "fingers do not match",
+            Self::NotFound => "no matching finger found",
+            Self::EnrollMismatch => "failed to combine character files",
+            Self::BadLocation => "location beyond the library",
+            Self::DbRangeErr => "error reading template from library",
+            Self::UploadFeatureFail => "error uploading template",
+            Self::PacketResponseFail => "module can't receive the following data packets",
+            Self::UploadImageFail => "error uploading image",
+            Self::DeleteFail => "failed to delete the template",
+            Self::DbClearFail => "failed to clear the library",
+            Self::WrongPassword => "wrong password",
+            Self::InvalidImage => "image invalid",
+            Self::FlashErr => "error writing flash",
+            Self::InvalidReg => "invalid register number",
+            Self::WrongNotepadPage => "wrong notepad page number",
+            Self::CommPortFail => "failed to operate communication port",
+        };
+
+        write!(f, "{}", message)
+    }
+}
+
+/// Errors that can occur while driving the sensor.
+#[derive(Debug)]
+pub enum Error {
+    /// An I/O-level failure: the transport failed (e.g. a timeout or a
+    /// dropped connection), a reply was malformed (bad start code, address,
+    /// packet type, length or checksum), or a search was issued before the
+    /// library size was known.
+    Transport(io::Error),
+    /// The sensor replied with a known, non-`Ok` confirmation code.
+    Protocol(ConfirmationCode),
+    /// The sensor replied with a status byte outside the known code table.
+    UnknownCode(u8),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transport(e) => write!(f, "transport error: {}", e),
+            Self::Protocol(code) => write!(f, "sensor reported an error: {}", code),
+            Self::UnknownCode(byte) => write!(f, "sensor reported an unknown code: {:#04X}", byte),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Transport(e) => Some(e),
+            Self::Protocol(_) | Self::UnknownCode(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Transport(e)
+    }
+}
+
+/// Maps a raw status byte to `Ok(())` or the matching [`Error`] variant.
+pub(crate) fn check_code(byte: u8) -> Result<(), Error> {
+    match ConfirmationCode::from_u8(byte) {
+        Ok(ConfirmationCode::Ok) => Ok(()),
+        Ok(code) => Err(Error::Protocol(code)),
+        Err(byte) => Err(Error::UnknownCode(byte)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_u8_decodes_known_codes() {
+        assert_eq!(ConfirmationCode::from_u8(0x00), Ok(ConfirmationCode::Ok));
+        assert_eq!(
+            ConfirmationCode::from_u8(0x02),
+            Ok(ConfirmationCode::NoFinger)
+        );
+        assert_eq!(
+            ConfirmationCode::from_u8(0x0A),
+            Ok(ConfirmationCode::EnrollMismatch)
+        );
+        assert_eq!(
+            ConfirmationCode::from_u8(0x18),
+            Ok(ConfirmationCode::FlashErr)
+        );
+    }
+
+    #[test]
+    fn from_u8_rejects_unknown_codes() {
+        assert_eq!(ConfirmationCode::from_u8(0xFE), Err(0xFE));
+    }
+
+    #[test]
+    fn try_from_matches_from_u8() {
+        assert_eq!(
+            ConfirmationCode::try_from(0x03),
+            ConfirmationCode::from_u8(0x03)
+        );
+    }
+
+    #[test]
+    fn check_code_maps_ok_to_unit() {
+        assert!(check_code(0x00).is_ok());
+    }
+
+    #[test]
+    fn check_code_maps_known_error_to_protocol_variant() {
+        match check_code(0x0B) {
+            Err(Error::Protocol(ConfirmationCode::BadLocation)) => {}
+            other => panic!("expected Error::Protocol(BadLocation), got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn check_code_maps_unknown_byte_to_unknown_code() {
+        match check_code(0xFE) {
+            Err(Error::UnknownCode(0xFE)) => {}
+            other => panic!("expected Error::UnknownCode(0xFE), got {:?}", other),
+        }
+    }
+}
@@ -1,13 +1,25 @@
 #![forbid(unsafe_code)]
 
+pub mod asynch;
+pub mod error;
+mod packet;
+pub mod session;
+pub mod transport;
+
 use byteorder::{BigEndian, ByteOrder, ReadBytesExt};
-use serialport::{self, SerialPort};
 use std::io::{self, Read, Write};
 use std::vec::Vec;
 
+pub use asynch::{AsyncDevice, AsyncTransport};
+pub use error::{ConfirmationCode, Error};
+pub use session::KeepAlive;
+pub use transport::{DeviceOptions, Transport};
+
 const STARTCODE: u16 = 0xEF01;
 const COMMANDPACKET: u8 = 0x1;
 const ACKPACKET: u8 = 0x7;
+const DATAPACKET: u8 = 0x2;
+const ENDDATAPACKET: u8 = 0x8;
 
 const VERIFYPASSWORD: u8 = 0x13;
 const TEMPLATECOUNT: u8 = 0x1D;
@@ -16,24 +28,26 @@ const READSYSPARAM: u8 = 0x0F;
 const GETIMAGE: u8 = 0x01;
 const IMAGE2TZ: u8 = 0x02;
 const FINGERPRINTSEARCH: u8 = 0x04;
+const HISPEEDSEARCH: u8 = 0x1B;
 const REGMODEL: u8 = 0x05;
 const STORE: u8 = 0x06;
 const DELETE: u8 = 0x0C;
-
-pub const OK: u8 = 0x0;
-pub const NOFINGER: u8 = 0x02;
-pub const IMAGEFAIL: u8 = 0x03;
-pub const IMAGEMESS: u8 = 0x06;
-pub const FEATUREFAIL: u8 = 0x07;
-pub const INVALIDIMAGE: u8 = 0x15;
-pub const HISPEEDSEARCH: u8 = 0x1B;
-pub const ENROLLMISMATCH: u8 = 0x0A;
-pub const BADLOCATION: u8 = 0x0B;
-pub const FLASHERR: u8 = 0x18;
+const UPCHAR: u8 = 0x08;
+const DOWNCHAR: u8 = 0x09;
+const UPIMAGE: u8 = 0x0A;
+const HANDSHAKE: u8 = 0x35;
+const LOADCHAR: u8 = 0x07;
+const MATCH: u8 = 0x03;
+const EMPTY: u8 = 0x0D;
+const READINDEXTABLE: u8 = 0x1F;
+
+/// How many times a command is re-sent after a framing or timeout error
+/// before `dispatch` gives up and surfaces the error.
+const MAX_RETRIES: u32 = 2;
 
 pub struct Device {
     _debug: bool,
-    uart: Box<dyn SerialPort>,
+    transport: Box<dyn Transport + Send>,
     status_register: Option<u16>,
     system_id: Option<u16>,
     library_size: Option<u16>,
@@ -48,12 +62,19 @@ pub struct Device {
 }
 
 impl Device {
-    pub fn new(address: Vec<u8>, password: Vec<u8>, uart: Box<dyn SerialPort>) -> Self {
+    pub fn new<T: Transport + Send + 'static>(mut transport: T, options: DeviceOptions) -> Self {
+        transport
+            .set_read_timeout(std::time::Duration::from_millis(options.read_timeout_ms))
+            .expect("Failed to set read timeout");
+        transport
+            .set_write_timeout(std::time::Duration::from_millis(options.write_timeout_ms))
+            .expect("Failed to set write timeout");
+
         let mut device = Self {
             _debug: false,
-            uart,
-            address,
-            password,
+            transport: Box::new(transport),
+            address: options.address,
+            password: options.password,
             status_register: None,
             system_id: None,
             library_size: None,
@@ -65,7 +86,7 @@ impl Device {
             confidence: 0,
         };
 
-        if !device.verify_password() {
+        if device.verify_password().is_err() {
             panic!("Failed to find sensor, check wiring!");
         }
 
@@ -76,19 +97,29 @@ impl Device {
         device
     }
 
-    pub fn verify_password(&mut self) -> bool {
+    pub fn verify_password(&mut self) -> Result<(), Error> {
         let packet: Vec<u8> = std::iter::once(VERIFYPASSWORD)
             .chain(self.password.iter().cloned())
             .collect();
 
-        if let Err(e) = self.send_packet(&packet) {
-            eprintln!("Failed to send the packet: {}", e);
-            return false;
-        }
+        let r = self.dispatch(&packet, 12)?;
 
-        let r = self.get_packet(12).unwrap_or_else(|_| vec![0; 12]);
+        error::check_code(r[0])
+    }
 
-        r[0] == OK
+    /// Lightweight ping used to check that the sensor is still alive, e.g.
+    /// from a periodic keep-alive (see [`crate::session`]).
+    pub fn handshake(&mut self) -> Result<(), Error> {
+        let r = self.dispatch(&[HANDSHAKE], 12)?;
+        error::check_code(r[0])
+    }
+
+    /// Re-runs the password check and re-reads the system parameters
+    /// without reopening the transport, recovering a session after a link
+    /// drop instead of panicking like `new` does on first connect.
+    pub fn soft_reset(&mut self) -> Result<(), Error> {
+        self.verify_password()?;
+        self.read_sysparam()
     }
 
     pub fn send_packet(&mut self, data: &[u8]) -> io::Result<()> {
@@ -110,57 +141,181 @@ impl Device {
         self.print_debug("send_packet length:", packet.len(), "bytes");
         self.print_debug("send_packet data:", &packet, "hex");
 
-        self.uart.write_all(&packet)?;
+        self.transport.write_all(&packet)?;
 
         Ok(())
     }
 
-    pub fn get_packet(&mut self, expected: usize) -> io::Result<Vec<u8>> {
+    pub fn get_packet(&mut self, expected: usize) -> Result<Vec<u8>, Error> {
         let mut res = vec![0; expected];
 
-        self.uart.read_exact(&mut res)?;
+        self.transport.read_exact(&mut res)?;
+
+        let reply = packet::parse_ack_packet(&res, &self.address)?;
+
+        self.print_debug("_get_packet reply:", &reply, "hex");
+
+        Ok(reply)
+    }
+
+    /// Retries `attempt` up to `MAX_RETRIES` times if it fails with a
+    /// framing or timeout error, so a single dropped byte doesn't
+    /// permanently fail the call. Used by `dispatch` for single command/ACK
+    /// round trips, and by `upload_char`/`download_char`/`upload_image` to
+    /// restart an entire multi-packet transfer from its initiating command,
+    /// since there's no safe way to resume one mid-stream (see
+    /// `read_data_packets`/`write_data_packets`).
+    fn retry<T>(&mut self, mut attempt: impl FnMut(&mut Self) -> Result<T, Error>) -> Result<T, Error> {
+        let mut tries = 0;
+
+        loop {
+            match attempt(self) {
+                Ok(value) => return Ok(value),
+                Err(Error::Transport(e)) if tries < MAX_RETRIES && is_retryable(&e) => {
+                    tries += 1;
+                    continue;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
 
-        let start = (&res[0..2]).read_u16::<BigEndian>().unwrap();
+    /// Sends a command packet and reads back its ACK, retrying on framing or
+    /// timeout errors via `retry`.
+    fn dispatch(&mut self, data: &[u8], expected: usize) -> Result<Vec<u8>, Error> {
+        self.retry(|device| {
+            device.send_packet(data)?;
+            Ok(device.get_packet(expected)?)
+        })
+    }
+
+    fn send_data_packet(&mut self, packet_type: u8, data: &[u8]) -> io::Result<()> {
+        let mut packet = vec![(STARTCODE >> 8) as u8, (STARTCODE & 0xFF) as u8];
+
+        packet.extend_from_slice(&self.address);
+        packet.push(packet_type);
+
+        let length = data.len() + 2;
+        packet.push((length >> 8) as u8);
+        packet.push((length & 0xFF) as u8);
+
+        packet.extend_from_slice(data);
+
+        let checksum: u16 = packet[6..].iter().map(|&byte| byte as u16).sum();
+        packet.push((checksum >> 8) as u8);
+        packet.push((checksum & 0xFF) as u8);
+
+        self.print_debug("send_data_packet length:", packet.len(), "bytes");
+        self.print_debug("send_data_packet data:", &packet, "hex");
+
+        self.transport.write_all(&packet)
+    }
+
+    fn read_data_packet(&mut self) -> Result<(u8, Vec<u8>), Error> {
+        let mut header = [0u8; 9];
+        self.transport.read_exact(&mut header)?;
+
+        let start = (&header[0..2]).read_u16::<BigEndian>().unwrap();
         if start != STARTCODE {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "Incorrect packet data",
-            ));
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Incorrect packet data").into());
         }
 
-        let addr = res[2..6].to_vec();
+        let addr = header[2..6].to_vec();
         if addr != self.address {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "Incorrect address",
-            ));
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Incorrect address").into());
         }
 
-        let packet_type = res[6];
-        let length = (&res[7..9]).read_u16::<BigEndian>().unwrap() as usize;
+        let packet_type = header[6];
+        let length = (&header[7..9]).read_u16::<BigEndian>().unwrap() as usize;
 
-        if packet_type != ACKPACKET {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "Incorrect packet data",
-            ));
+        if length < 2 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Packet length too short").into());
         }
 
-        let reply = res[9..9 + (length - 2)].to_vec();
+        let mut rest = vec![0; length];
+        self.transport.read_exact(&mut rest)?;
 
-        self.print_debug("_get_packet reply:", &reply, "hex");
+        let payload = rest[..length - 2].to_vec();
+        let checksum = BigEndian::read_u16(&rest[length - 2..]);
 
-        Ok(reply)
+        let computed: u16 = header[6..9]
+            .iter()
+            .chain(payload.iter())
+            .map(|&byte| byte as u16)
+            .sum();
+
+        if computed != checksum {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Checksum mismatch").into());
+        }
+
+        self.print_debug("read_data_packet payload:", &payload, "hex");
+
+        Ok((packet_type, payload))
+    }
+
+    /// Reads data packets until the end-of-data packet, concatenating their
+    /// payloads into a single buffer (used by `upload_char`/`upload_image`).
+    ///
+    /// Deliberately *not* retried packet-by-packet: unlike `dispatch`, a
+    /// failed read partway through a transfer has no restart point the
+    /// sensor recognizes, so retrying just the failed packet would resume
+    /// mid-stream against a sensor that's still framed at the point of
+    /// failure. Callers retry the whole command instead (see
+    /// `upload_char`/`upload_image`).
+    fn read_data_packets(&mut self) -> Result<Vec<u8>, Error> {
+        let mut buffer = Vec::new();
+
+        loop {
+            let (packet_type, payload) = self.read_data_packet()?;
+            buffer.extend_from_slice(&payload);
+
+            if packet_type == ENDDATAPACKET {
+                break;
+            }
+        }
+
+        Ok(buffer)
     }
 
-    pub fn read_sysparam(&mut self) -> io::Result<u8> {
-        self.send_packet(&[READSYSPARAM])?;
+    /// Splits `data` into `data_packet_size`-sized data packets and sends
+    /// them, marking the final chunk as the end-of-data packet (used by
+    /// `download_char`).
+    ///
+    /// Deliberately *not* retried packet-by-packet: if a chunk times out
+    /// after partially landing on the wire, resending just that chunk could
+    /// duplicate bytes into a stream the sensor has no framing to
+    /// de-duplicate, corrupting the template it ends up storing. Callers
+    /// retry the whole command instead (see `download_char`), which
+    /// re-sends `DOWNCHAR` and restarts the transfer from the first byte.
+    fn write_data_packets(&mut self, data: &[u8]) -> Result<(), Error> {
+        let chunk_size = self.data_packet_byte_size();
+        let chunks: Vec<&[u8]> = data.chunks(chunk_size).collect();
+        let last = chunks.len().saturating_sub(1);
+
+        for (i, chunk) in chunks.iter().enumerate() {
+            let packet_type = if i == last { ENDDATAPACKET } else { DATAPACKET };
+            self.send_data_packet(packet_type, chunk)?;
+        }
 
-        let r = self.get_packet(28)?;
+        Ok(())
+    }
 
-        if r[0] != OK {
-            return Err(io::Error::new(io::ErrorKind::Other, "Command failed."));
+    /// Decodes the sensor's `data_packet_size` system parameter (a 0-3 code
+    /// read by `read_sysparam`) into an actual byte count.
+    fn data_packet_byte_size(&self) -> usize {
+        match self.data_packet_size {
+            Some(0) => 32,
+            Some(1) => 64,
+            Some(2) => 128,
+            Some(3) => 256,
+            _ => 32,
         }
+    }
+
+    pub fn read_sysparam(&mut self) -> Result<(), Error> {
+        let r = self.dispatch(&[READSYSPARAM], 28)?;
+
+        error::check_code(r[0])?;
 
         self.status_register = Some((&r[1..3]).read_u16::<BigEndian>()?);
         self.system_id = Some((&r[3..5]).read_u16::<BigEndian>()?);
@@ -170,12 +325,11 @@ impl Device {
         self.data_packet_size = Some((&r[13..15]).read_u16::<BigEndian>()?);
         self.baudrate = Some((&r[15..17]).read_u16::<BigEndian>()?);
 
-        Ok(r[0])
+        Ok(())
     }
 
-    pub fn count_templates(&mut self) -> io::Result<u8> {
-        let _ = self.send_packet(&[TEMPLATECOUNT]);
-        let r = self.get_packet(14)?;
+    pub fn count_templates(&mut self) -> Result<(), Error> {
+        let r = self.dispatch(&[TEMPLATECOUNT], 14)?;
 
         if r.len() >= 3 {
             self.template_count = BigEndian::read_u16(&r[1..3]);
@@ -183,67 +337,59 @@ impl Device {
             self.template_count = 0;
         }
 
-        if r[0] == OK {
-            Ok(r[0])
-        } else {
-            Err(io::Error::new(io::ErrorKind::Other, "Command failed."))
-        }
+        error::check_code(r[0])
     }
 
-    pub fn get_image(&mut self) -> io::Result<u8> {
-        let _ = self.send_packet(&[GETIMAGE]);
-        let r = self.get_packet(12)?;
-        Ok(r[0])
+    pub fn get_image(&mut self) -> Result<(), Error> {
+        let r = self.dispatch(&[GETIMAGE], 12)?;
+        error::check_code(r[0])
     }
 
-    pub fn image_2_tz(&mut self, slot: u8) -> io::Result<u8> {
-        let _ = self.send_packet(&[IMAGE2TZ, slot]);
-        let r = self.get_packet(12)?;
-        Ok(r[0])
+    pub fn image_2_tz(&mut self, slot: u8) -> Result<(), Error> {
+        let r = self.dispatch(&[IMAGE2TZ, slot], 12)?;
+        error::check_code(r[0])
     }
 
-    pub fn finger_search(&mut self) -> io::Result<u8> {
-        if self.library_size.is_none() {
-            return Err(io::Error::new(io::ErrorKind::Other, "Library size not set"));
-        }
+    pub fn finger_search(&mut self) -> Result<(), Error> {
         let capacity = match self.library_size {
             Some(capacity) => capacity,
-            None => return Err(io::Error::new(io::ErrorKind::Other, "Library size not set")),
+            None => return Err(io::Error::new(io::ErrorKind::Other, "Library size not set").into()),
         };
-        let _ = self.send_packet(&[
-            FINGERPRINTSEARCH,
-            0x01,
-            0x00,
-            0x00,
-            (capacity >> 8) as u8,
-            (capacity & 0xFF) as u8,
-        ]);
-        let r = self.get_packet(16)?;
+        let r = self.dispatch(
+            &[
+                FINGERPRINTSEARCH,
+                0x01,
+                0x00,
+                0x00,
+                (capacity >> 8) as u8,
+                (capacity & 0xFF) as u8,
+            ],
+            16,
+        )?;
         self.finger_id = BigEndian::read_u16(&r[1..3]);
         self.confidence = BigEndian::read_u16(&r[3..5]);
         self.print_debug("finger_search packet:", &r, "hex");
-        Ok(r[0])
+        error::check_code(r[0])
     }
 
-    pub fn finger_fast_search(&mut self) -> io::Result<u8> {
+    pub fn finger_fast_search(&mut self) -> Result<(), Error> {
         let _ = self.read_sysparam();
         let capacity = match self.library_size {
             Some(capacity) => capacity,
-            None => return Err(io::Error::new(io::ErrorKind::Other, "Library size not set")),
+            None => return Err(io::Error::new(io::ErrorKind::Other, "Library size not set").into()),
         };
 
-        let packet = vec![
-            HISPEEDSEARCH,
-            0x01,
-            0x00,
-            0x00,
-            (capacity >> 8) as u8,
-            (capacity & 0xFF) as u8,
-        ];
-
-        let _ = self.send_packet(&packet);
-
-        let r = self.get_packet(16)?;
+        let r = self.dispatch(
+            &[
+                HISPEEDSEARCH,
+                0x01,
+                0x00,
+                0x00,
+                (capacity >> 8) as u8,
+                (capacity & 0xFF) as u8,
+            ],
+            16,
+        )?;
 
         let finger_data = &r[1..5];
         self.finger_id = u16::from_be_bytes([finger_data[0], finger_data[1]]);
@@ -251,30 +397,138 @@ impl Device {
 
         self.print_debug("finger_fast_search packet:", &r, "hex");
 
-        Ok(r[0])
+        error::check_code(r[0])
     }
 
-    pub fn create_model(&mut self) -> io::Result<u8> {
-        let _ = self.send_packet(&[REGMODEL]);
-        let r = self.get_packet(12)?;
-        Ok(r[0])
+    pub fn create_model(&mut self) -> Result<(), Error> {
+        let r = self.dispatch(&[REGMODEL], 12)?;
+        error::check_code(r[0])
     }
 
-    pub fn store_model(&mut self, location: u16, slot: u8) -> io::Result<u8> {
-        self.send_packet(&[STORE, slot, (location >> 8) as u8, (location & 0xFF) as u8])?;
-
-        let r = self.get_packet(12)?;
-        Ok(r[0])
+    pub fn store_model(&mut self, location: u16, slot: u8) -> Result<(), Error> {
+        let r = self.dispatch(
+            &[STORE, slot, (location >> 8) as u8, (location & 0xFF) as u8],
+            12,
+        )?;
+        error::check_code(r[0])
     }
 
-    pub fn delete_model(&mut self, location: u16) -> io::Result<u8> {
+    pub fn delete_model(&mut self, location: u16) -> Result<(), Error> {
         let high_byte = (location >> 8) as u8;
         let low_byte = (location & 0xFF) as u8;
 
-        self.send_packet(&[DELETE, high_byte, low_byte, 0x00, 0x01])?;
+        let r = self.dispatch(&[DELETE, high_byte, low_byte, 0x00, 0x01], 12)?;
+        error::check_code(r[0])
+    }
+
+    /// Uploads the character file stored in the given character buffer slot
+    /// (1 or 2), returning the raw template bytes for backup or host-side
+    /// matching.
+    ///
+    /// A framing or timeout error anywhere in the transfer restarts the
+    /// whole command, re-sending `UPCHAR` to give the sensor a fresh, aligned
+    /// start, rather than resuming the read mid-stream (see
+    /// `read_data_packets`).
+    pub fn upload_char(&mut self, slot: u8) -> Result<Vec<u8>, Error> {
+        self.retry(|device| {
+            let r = device.dispatch(&[UPCHAR, slot], 12)?;
+            error::check_code(r[0])?;
+
+            device.read_data_packets()
+        })
+    }
+
+    /// Downloads a previously uploaded template into the given character
+    /// buffer slot (1 or 2), ready to be combined with `create_model`.
+    ///
+    /// A framing or timeout error anywhere in the transfer restarts the
+    /// whole command, re-sending `DOWNCHAR` and the entire template from the
+    /// first byte, rather than resending only the chunk that failed (see
+    /// `write_data_packets`) — the sensor has no framing to de-duplicate a
+    /// partially-landed chunk that gets resent in place.
+    pub fn download_char(&mut self, slot: u8, template: &[u8]) -> Result<(), Error> {
+        self.retry(|device| {
+            let r = device.dispatch(&[DOWNCHAR, slot], 12)?;
+            error::check_code(r[0])?;
+
+            device.write_data_packets(template)
+        })
+    }
+
+    /// Uploads the raw fingerprint image captured by the last `get_image`.
+    ///
+    /// A framing or timeout error anywhere in the transfer restarts the
+    /// whole command, re-sending `UPIMAGE` to give the sensor a fresh,
+    /// aligned start, rather than resuming the read mid-stream (see
+    /// `read_data_packets`).
+    pub fn upload_image(&mut self) -> Result<Vec<u8>, Error> {
+        self.retry(|device| {
+            let r = device.dispatch(&[UPIMAGE], 12)?;
+            error::check_code(r[0])?;
+
+            device.read_data_packets()
+        })
+    }
+
+    /// Parses the sensor's occupancy bitmap into the list of slot IDs that
+    /// currently hold a template, so callers can show what's enrolled or
+    /// pick the next free slot instead of guessing a location.
+    pub fn read_index_table(&mut self) -> Result<Vec<u16>, Error> {
+        let r = self.dispatch(&[READINDEXTABLE], 44)?;
+        error::check_code(r[0])?;
+
+        let mut used = Vec::new();
+        for (byte_index, byte) in r[1..33].iter().enumerate() {
+            for bit in 0..8 {
+                if byte & (1 << bit) != 0 {
+                    used.push((byte_index * 8 + bit) as u16);
+                }
+            }
+        }
+
+        Ok(used)
+    }
+
+    /// Clears every template from the sensor's library.
+    pub fn empty_database(&mut self) -> Result<(), Error> {
+        let r = self.dispatch(&[EMPTY], 12)?;
+        error::check_code(r[0])
+    }
+
+    /// Loads the template stored at `location` into character buffer 1 or 2.
+    fn load_char(&mut self, location: u16, slot: u8) -> Result<(), Error> {
+        let r = self.dispatch(
+            &[LOADCHAR, slot, (location >> 8) as u8, (location & 0xFF) as u8],
+            12,
+        )?;
+        error::check_code(r[0])
+    }
 
-        let r = self.get_packet(12)?;
-        Ok(r[0])
+    /// Precisely compares whatever is currently in character buffers 1 and
+    /// 2, returning the match confidence score.
+    fn match_buffers(&mut self) -> Result<u16, Error> {
+        let r = self.dispatch(&[MATCH], 14)?;
+        let confidence = BigEndian::read_u16(&r[1..3]);
+        self.print_debug("match_buffers packet:", &r, "hex");
+        error::check_code(r[0])?;
+        Ok(confidence)
+    }
+
+    /// One-to-one compares two stored templates by slot, for targeted
+    /// verification instead of a library-wide `finger_search`.
+    pub fn match_templates(&mut self, slot_a: u16, slot_b: u16) -> Result<u16, Error> {
+        self.load_char(slot_a, 1)?;
+        self.load_char(slot_b, 2)?;
+        self.match_buffers()
+    }
+
+    /// Captures the finger on the sensor and one-to-one verifies it against
+    /// the template stored at `slot`, returning the match confidence score.
+    pub fn verify(&mut self, slot: u16) -> Result<u16, Error> {
+        self.get_image()?;
+        self.image_2_tz(1)?;
+        self.load_char(slot, 2)?;
+        self.match_buffers()
     }
 
     fn print_debug(&self, message: &str, data: impl std::fmt::Debug, data_type: &str) {
@@ -290,6 +544,199 @@ impl Device {
 
 impl Drop for Device {
     fn drop(&mut self) {
-        self.uart.flush().unwrap();
+        // Best-effort: a transport whose link already dropped (the case
+        // `KeepAlive`/retry exist to tolerate) will fail this flush, and
+        // that's not worth panicking the caller over on the way out.
+        let _ = self.transport.flush();
+    }
+}
+
+/// Errors worth retrying: a dropped or out-of-sync byte stream rather than
+/// a sensor-reported failure, which `dispatch` would only repeat.
+fn is_retryable(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::InvalidData | io::ErrorKind::TimedOut | io::ErrorKind::UnexpectedEof
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::time::Duration;
+
+    /// In-memory `Transport` for unit tests: reads are served from a
+    /// preloaded byte queue instead of real hardware.
+    struct MockTransport {
+        to_read: VecDeque<u8>,
+    }
+
+    impl MockTransport {
+        fn new(to_read: Vec<u8>) -> Self {
+            Self {
+                to_read: to_read.into(),
+            }
+        }
+    }
+
+    impl Read for MockTransport {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let mut n = 0;
+            while n < buf.len() {
+                match self.to_read.pop_front() {
+                    Some(byte) => {
+                        buf[n] = byte;
+                        n += 1;
+                    }
+                    None => break,
+                }
+            }
+            Ok(n)
+        }
+    }
+
+    impl Write for MockTransport {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Transport for MockTransport {
+        fn set_read_timeout(&mut self, _timeout: Duration) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn set_write_timeout(&mut self, _timeout: Duration) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Builds an ACK packet (start code, address, `ACKPACKET`, length,
+    /// confirmation code, payload, checksum) the way the sensor would.
+    fn ack_packet(address: &[u8], code: u8, payload: &[u8]) -> Vec<u8> {
+        let mut packet = vec![(STARTCODE >> 8) as u8, (STARTCODE & 0xFF) as u8];
+        packet.extend_from_slice(address);
+        packet.push(ACKPACKET);
+
+        let length = 1 + payload.len() + 2;
+        packet.push((length >> 8) as u8);
+        packet.push((length & 0xFF) as u8);
+        packet.push(code);
+        packet.extend_from_slice(payload);
+
+        let checksum: u16 = packet[6..].iter().map(|&byte| byte as u16).sum();
+        packet.push((checksum >> 8) as u8);
+        packet.push((checksum & 0xFF) as u8);
+
+        packet
+    }
+
+    /// Builds a `Device` whose construction-time `verify_password` and
+    /// `read_sysparam` calls are satisfied by canned ACKs, with `extra`
+    /// queued behind them for the test to consume.
+    fn test_device(extra: Vec<u8>) -> Device {
+        let address = vec![0xFF, 0xFF, 0xFF, 0xFF];
+
+        let mut bytes = ack_packet(&address, 0x00, &[]);
+        bytes.extend(ack_packet(
+            &address,
+            0x00,
+            &[
+                0, 0, // status_register
+                0, 0, // system_id
+                0, 100, // library_size
+                0, 0, // security_level
+                0xFF, 0xFF, 0xFF, 0xFF, // address
+                0, 0, // data_packet_size
+                0, 1, // baudrate
+            ],
+        ));
+        bytes.extend(extra);
+
+        let transport = MockTransport::new(bytes);
+        let options = DeviceOptions {
+            address,
+            password: vec![0, 0, 0, 0],
+            read_timeout_ms: 100,
+            write_timeout_ms: 100,
+        };
+
+        Device::new(transport, options)
+    }
+
+    #[test]
+    fn read_data_packet_accepts_valid_checksum() {
+        let mut device = test_device(vec![]);
+        let packet = {
+            let mut p = vec![(STARTCODE >> 8) as u8, (STARTCODE & 0xFF) as u8];
+            p.extend_from_slice(&device.address.clone());
+            p.push(ENDDATAPACKET);
+            let payload = vec![1, 2, 3, 4];
+            let length = payload.len() + 2;
+            p.push((length >> 8) as u8);
+            p.push((length & 0xFF) as u8);
+            p.extend_from_slice(&payload);
+            let checksum: u16 = p[6..].iter().map(|&byte| byte as u16).sum();
+            p.push((checksum >> 8) as u8);
+            p.push((checksum & 0xFF) as u8);
+            p
+        };
+        device.transport = Box::new(MockTransport::new(packet));
+
+        let (packet_type, payload) = device.read_data_packet().expect("valid packet");
+        assert_eq!(packet_type, ENDDATAPACKET);
+        assert_eq!(payload, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn read_data_packet_rejects_bad_checksum() {
+        let mut device = test_device(vec![]);
+        let mut packet = vec![(STARTCODE >> 8) as u8, (STARTCODE & 0xFF) as u8];
+        packet.extend_from_slice(&device.address.clone());
+        packet.push(ENDDATAPACKET);
+        let payload = vec![1, 2, 3, 4];
+        let length = payload.len() + 2;
+        packet.push((length >> 8) as u8);
+        packet.push((length & 0xFF) as u8);
+        packet.extend_from_slice(&payload);
+        packet.push(0xFF);
+        packet.push(0xFF);
+        device.transport = Box::new(MockTransport::new(packet));
+
+        assert!(device.read_data_packet().is_err());
+    }
+
+    #[test]
+    fn read_data_packet_rejects_length_below_checksum_size() {
+        let mut device = test_device(vec![]);
+        let mut packet = vec![(STARTCODE >> 8) as u8, (STARTCODE & 0xFF) as u8];
+        packet.extend_from_slice(&device.address.clone());
+        packet.push(ENDDATAPACKET);
+        // length 1 is shorter than the 2-byte checksum it must contain.
+        packet.push(0);
+        packet.push(1);
+        packet.push(0xAB);
+        device.transport = Box::new(MockTransport::new(packet));
+
+        assert!(device.read_data_packet().is_err());
+    }
+
+    #[test]
+    fn read_index_table_decodes_occupancy_bitmap() {
+        let mut bitmap = vec![0u8; 32];
+        bitmap[0] = 0b0000_0011; // slots 0 and 1
+        bitmap[1] = 0b0000_0001; // slot 8
+
+        let address = vec![0xFF, 0xFF, 0xFF, 0xFF];
+        let extra = ack_packet(&address, 0x00, &bitmap);
+        let mut device = test_device(extra);
+
+        let used = device.read_index_table().expect("valid index table");
+        assert_eq!(used, vec![0, 1, 8]);
     }
 }
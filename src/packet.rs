@@ -0,0 +1,44 @@
+//! ACK-packet validation shared by [`crate::Device::get_packet`] and
+//! [`crate::asynch::AsyncDevice`]'s copy of the same logic.
+//!
+//! Reading the bytes off the wire differs between the blocking and async
+//! transports, but once a full packet has been read into memory, checking
+//! the start code, address, and packet type, and slicing out the reply
+//! payload is identical either way. Keeping that parsing here means a fix
+//! like the length-underflow check below only has to be made once.
+
+use byteorder::{BigEndian, ReadBytesExt};
+use std::io;
+
+use crate::error::Error;
+
+pub(crate) const STARTCODE: u16 = 0xEF01;
+pub(crate) const ACKPACKET: u8 = 0x7;
+
+/// Validates a full ACK packet already read into `res` (exactly
+/// `res.len()` bytes, as returned by a blocking or async `read_exact`),
+/// and returns its reply payload (the bytes between the length field and
+/// the checksum).
+pub(crate) fn parse_ack_packet(res: &[u8], address: &[u8]) -> Result<Vec<u8>, Error> {
+    let start = (&res[0..2]).read_u16::<BigEndian>().unwrap();
+    if start != STARTCODE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Incorrect packet data").into());
+    }
+
+    let addr = &res[2..6];
+    if addr != address {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Incorrect address").into());
+    }
+
+    let packet_type = res[6];
+    if packet_type != ACKPACKET {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Incorrect packet data").into());
+    }
+
+    let length = (&res[7..9]).read_u16::<BigEndian>().unwrap() as usize;
+    if length < 2 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Packet length too short").into());
+    }
+
+    Ok(res[9..9 + (length - 2)].to_vec())
+}
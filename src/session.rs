@@ -0,0 +1,86 @@
+//! Keep-alive layer for a long-lived [`crate::Device`] connection.
+//!
+//! `Device` verifies the password once on open and otherwise assumes the
+//! link stays up. [`KeepAlive`] runs a background thread that periodically
+//! calls [`crate::Device::handshake`] so a dead link is noticed even if the
+//! application isn't actively issuing commands.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::Device;
+
+/// How often the keep-alive thread rechecks the stop flag while waiting out
+/// its interval, so `stop()`/`drop` don't block for a whole (potentially
+/// long) keep-alive period.
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Sleeps for `duration` in `STOP_POLL_INTERVAL` slices, checking `stop`
+/// between each. Returns `false` as soon as `stop` is seen set (including
+/// before the full duration has elapsed), `true` otherwise.
+fn sleep_interruptibly(stop: &AtomicBool, duration: Duration) -> bool {
+    let mut remaining = duration;
+
+    while remaining > Duration::ZERO {
+        if stop.load(Ordering::SeqCst) {
+            return false;
+        }
+
+        let slice = remaining.min(STOP_POLL_INTERVAL);
+        thread::sleep(slice);
+        remaining -= slice;
+    }
+
+    !stop.load(Ordering::SeqCst)
+}
+
+/// Handle to a running keep-alive thread. Dropping it or calling
+/// [`KeepAlive::stop`] signals the thread to exit and waits for it to
+/// finish.
+pub struct KeepAlive {
+    stop: Arc<AtomicBool>,
+    join: Option<JoinHandle<()>>,
+}
+
+impl KeepAlive {
+    /// Spawns a thread that calls `device.handshake()` every `interval`
+    /// until stopped, ignoring individual handshake failures (a single
+    /// missed beat isn't fatal; callers that care should check the result
+    /// of their own commands).
+    pub fn start(device: Arc<Mutex<Device>>, interval: Duration) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_signal = stop.clone();
+
+        let join = thread::spawn(move || {
+            while sleep_interruptibly(&stop_signal, interval) {
+                if let Ok(mut device) = device.lock() {
+                    let _ = device.handshake();
+                }
+            }
+        });
+
+        Self {
+            stop,
+            join: Some(join),
+        }
+    }
+
+    /// Signals the keep-alive thread to stop and waits for it to exit.
+    pub fn stop(mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(join) = self.join.take() {
+            let _ = join.join();
+        }
+    }
+}
+
+impl Drop for KeepAlive {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(join) = self.join.take() {
+            let _ = join.join();
+        }
+    }
+}
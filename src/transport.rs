@@ -0,0 +1,58 @@
+//! Transport abstraction so the protocol layer isn't tied to a local UART.
+//!
+//! [`crate::Device`] only needs a byte-oriented channel with configurable
+//! read/write timeouts to send and receive packets; it doesn't care whether
+//! that channel is a local serial port, a TCP socket to a network gateway,
+//! or a BLE bridge. `Transport` is that channel, and `Box<dyn SerialPort>`
+//! is just the one implementation the crate ships out of the box.
+
+use std::io::{self, Read, Write};
+use std::time::Duration;
+
+/// A byte-oriented channel `Device` sends commands over and reads replies
+/// from, with independently configurable read and write timeouts.
+pub trait Transport: Read + Write {
+    /// Sets how long a read may block before timing out.
+    fn set_read_timeout(&mut self, timeout: Duration) -> io::Result<()>;
+
+    /// Sets how long a write may block before timing out.
+    fn set_write_timeout(&mut self, timeout: Duration) -> io::Result<()>;
+}
+
+/// `serialport` has a single timeout shared by reads and writes, so both
+/// setters change that one value and whichever is called last applies to
+/// both directions (in `Device::new`, the write timeout).
+impl Transport for Box<dyn serialport::SerialPort> {
+    fn set_read_timeout(&mut self, timeout: Duration) -> io::Result<()> {
+        self.as_mut()
+            .set_timeout(timeout)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+    }
+
+    fn set_write_timeout(&mut self, timeout: Duration) -> io::Result<()> {
+        self.as_mut()
+            .set_timeout(timeout)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+    }
+}
+
+/// Options used to construct a [`crate::Device`]: the 4-byte sensor address
+/// and password sent with every packet, plus how long the transport may
+/// block on a read or write before giving up.
+pub struct DeviceOptions {
+    pub address: Vec<u8>,
+    pub password: Vec<u8>,
+    pub read_timeout_ms: u64,
+    pub write_timeout_ms: u64,
+}
+
+impl Default for DeviceOptions {
+    fn default() -> Self {
+        Self {
+            address: vec![0xFF; 4],
+            password: vec![0; 4],
+            read_timeout_ms: 900,
+            write_timeout_ms: 900,
+        }
+    }
+}